@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// Write `entries` (relative to `base`) into a gzipped tarball at `dest`.
+///
+/// Directories are added recursively; entries that do not exist are skipped.
+pub fn create(base: &Path, entries: &[PathBuf], dest: &Path) -> Result<usize> {
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    let file =
+        File::create(dest).with_context(|| format!("Failed to create {}", dest.display()))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    let mut added = 0;
+    for entry in entries {
+        let path = base.join(entry);
+        let result = if path.is_dir() {
+            builder.append_dir_all(entry, &path)
+        } else if path.is_file() {
+            builder.append_path_with_name(&path, entry)
+        } else {
+            continue;
+        };
+        result.with_context(|| format!("Failed to archive {}", path.display()))?;
+        added += 1;
+    }
+
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .with_context(|| format!("Failed to write {}", dest.display()))?;
+
+    Ok(added)
+}
+
+/// Unpack a tarball written by [`create`] into `dest`
+pub fn extract(archive: &Path, dest: &Path) -> Result<()> {
+    let file =
+        File::open(archive).with_context(|| format!("Failed to open {}", archive.display()))?;
+
+    std::fs::create_dir_all(dest)
+        .with_context(|| format!("Failed to create {}", dest.display()))?;
+
+    // `unpack` refuses entries that would land outside `dest`
+    tar::Archive::new(GzDecoder::new(file))
+        .unpack(dest)
+        .with_context(|| format!("Failed to unpack {}", archive.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "cargo-pumpkin-archive-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn create_skips_missing_entries() {
+        let dir = scratch_dir("missing");
+        let base = dir.join("run");
+        std::fs::create_dir_all(base.join("plugins")).unwrap();
+        std::fs::write(base.join("pumpkin"), "server").unwrap();
+
+        let entries = [
+            PathBuf::from("pumpkin"),
+            PathBuf::from("plugins"),
+            PathBuf::from("build-info.json"),
+        ];
+        let added = create(&base, &entries, &dir.join("out.tar.gz")).unwrap();
+        assert_eq!(added, 2);
+
+        let added = create(&base, &[PathBuf::from("world")], &dir.join("empty.tar.gz")).unwrap();
+        assert_eq!(added, 0);
+    }
+
+    #[test]
+    fn extract_restores_only_the_archived_entries() {
+        let dir = scratch_dir("round-trip");
+        let base = dir.join("run");
+        std::fs::create_dir_all(base.join("plugins/nested")).unwrap();
+        std::fs::write(base.join("pumpkin"), "server").unwrap();
+        std::fs::write(base.join("plugins/nested/config.toml"), "a = 1").unwrap();
+        std::fs::write(base.join("server.log"), "not archived").unwrap();
+
+        let archive = dir.join("nested/dir/out.tar.gz");
+        create(
+            &base,
+            &[PathBuf::from("pumpkin"), PathBuf::from("plugins")],
+            &archive,
+        )
+        .unwrap();
+
+        let restored = dir.join("restored");
+        extract(&archive, &restored).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(restored.join("pumpkin")).unwrap(),
+            "server"
+        );
+        assert_eq!(
+            std::fs::read_to_string(restored.join("plugins/nested/config.toml")).unwrap(),
+            "a = 1"
+        );
+        assert!(!restored.join("server.log").exists());
+    }
+}
@@ -0,0 +1,192 @@
+use crate::release;
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::fs;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Hashes of the files the last sync wrote, keyed by their path in `.run`
+pub const MANIFEST_FILE: &str = ".asset-manifest";
+
+/// What a [`sync`] changed
+#[derive(Debug, Default)]
+pub struct SyncStats {
+    pub copied: usize,
+    pub unchanged: usize,
+    pub removed: usize,
+}
+
+/// Copy every file under each `(source, dest)` directory pair into `run_dir/dest`.
+///
+/// With `incremental`, files whose hash matches `.asset-manifest` are skipped
+/// and files the previous sync wrote that no longer exist in a source are
+/// deleted from `run_dir`. The manifest is rewritten either way so the next
+/// incremental sync has something to compare against.
+///
+/// Target directories are created up front, parents first; files are then
+/// hashed and copied by at most `jobs` tasks at a time.
+pub async fn sync(
+    run_dir: &Path,
+    dirs: &[(PathBuf, PathBuf)],
+    incremental: bool,
+    jobs: usize,
+) -> Result<SyncStats> {
+    let manifest_path = run_dir.join(MANIFEST_FILE);
+    let previous: BTreeMap<String, String> = if incremental && manifest_path.exists() {
+        let content = std::fs::read_to_string(&manifest_path)
+            .with_context(|| format!("Failed to read {}", MANIFEST_FILE))?;
+        parse_manifest(&content)
+    } else {
+        BTreeMap::new()
+    };
+
+    let mut files = Vec::new();
+    let mut parents = BTreeSet::new();
+    for (source, dest) in dirs {
+        if !source.is_dir() {
+            anyhow::bail!("Asset directory not found: {}", source.display());
+        }
+
+        for file in files_under(source)? {
+            let relative = dest.join(file.strip_prefix(source)?);
+            let key = relative.to_string_lossy().replace('\\', "/");
+            let target = run_dir.join(&relative);
+            if let Some(parent) = target.parent() {
+                parents.insert(parent.to_path_buf());
+            }
+            files.push((file, key, target));
+        }
+    }
+
+    // Sorted paths list every parent before its children
+    for parent in &parents {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    let previous = Arc::new(previous);
+    let permits = Arc::new(Semaphore::new(jobs.max(1)));
+    let mut tasks = JoinSet::new();
+    for (file, key, target) in files {
+        let previous = Arc::clone(&previous);
+        let permits = Arc::clone(&permits);
+        tasks.spawn(async move {
+            let _permit = permits.acquire_owned().await?;
+            let content = fs::read(&file)
+                .await
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let hash = release::sha256_hex(&content);
+
+            let unchanged = previous.get(&key) == Some(&hash) && target.exists();
+            if !unchanged {
+                fs::write(&target, &content)
+                    .await
+                    .with_context(|| format!("Failed to write {}", target.display()))?;
+            }
+            anyhow::Ok((key, hash, unchanged))
+        });
+    }
+
+    let mut stats = SyncStats::default();
+    let mut current = BTreeMap::new();
+    while let Some(result) = tasks.join_next().await {
+        let (key, hash, unchanged) = result.context("Asset copy task failed")??;
+        if unchanged {
+            stats.unchanged += 1;
+        } else {
+            stats.copied += 1;
+        }
+        current.insert(key, hash);
+    }
+
+    for key in removed_entries(&previous, &current) {
+        let target = run_dir.join(key);
+        if target.is_file() {
+            std::fs::remove_file(&target)
+                .with_context(|| format!("Failed to remove {}", target.display()))?;
+            stats.removed += 1;
+        }
+    }
+
+    std::fs::write(&manifest_path, serde_json::to_string_pretty(&current)?)
+        .with_context(|| format!("Failed to write {}", MANIFEST_FILE))?;
+
+    Ok(stats)
+}
+
+/// Entries of a `.asset-manifest`; a corrupt manifest only costs a full copy
+fn parse_manifest(content: &str) -> BTreeMap<String, String> {
+    serde_json::from_str(content).unwrap_or_default()
+}
+
+/// Paths the previous sync wrote that are no longer in any source
+fn removed_entries<'a>(
+    previous: &'a BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) -> Vec<&'a String> {
+    previous
+        .keys()
+        .filter(|key| !current.contains_key(*key))
+        .collect()
+}
+
+/// All files below `dir`, recursively
+fn files_under(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in
+            std::fs::read_dir(&dir).with_context(|| format!("Failed to read {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(key, hash)| (key.to_string(), hash.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_manifest_reads_written_manifests() {
+        let written = manifest(&[("plugins/a/config.toml", "ab12"), ("world/x.dat", "cd34")]);
+        let content = serde_json::to_string_pretty(&written).unwrap();
+        assert_eq!(parse_manifest(&content), written);
+    }
+
+    #[test]
+    fn parse_manifest_treats_corrupt_content_as_empty() {
+        assert!(parse_manifest("").is_empty());
+        assert!(parse_manifest("{\"truncated\": ").is_empty());
+        assert!(parse_manifest("[1, 2]").is_empty());
+    }
+
+    #[test]
+    fn removed_entries_lists_files_gone_from_the_sources() {
+        let previous = manifest(&[("a.txt", "1"), ("b.txt", "2"), ("c.txt", "3")]);
+        let current = manifest(&[("b.txt", "20"), ("d.txt", "4")]);
+        assert_eq!(removed_entries(&previous, &current), ["a.txt", "c.txt"]);
+    }
+
+    #[test]
+    fn removed_entries_is_empty_without_a_previous_sync() {
+        let current = manifest(&[("a.txt", "1")]);
+        assert!(removed_entries(&BTreeMap::new(), &current).is_empty());
+    }
+}
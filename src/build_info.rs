@@ -0,0 +1,39 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// Name of the build metadata file written into `.run`
+pub const BUILD_INFO_FILE: &str = "build-info.json";
+
+/// Description of the artifacts deployed into `.run`, read by external tooling
+#[derive(Debug, Serialize)]
+pub struct BuildInfo {
+    pub plugin_name: Option<String>,
+    pub plugin_version: Option<String>,
+    pub pumpkin_commit: Option<String>,
+    pub profile: String,
+    /// Seconds since the Unix epoch
+    pub built_at: u64,
+    pub server_binary: PathBuf,
+    pub plugins: Vec<PathBuf>,
+}
+
+impl BuildInfo {
+    /// Write the file through a temporary sibling so readers never see a partial file
+    pub async fn write(&self, run_dir: &Path) -> Result<()> {
+        let path = run_dir.join(BUILD_INFO_FILE);
+        let temp_path = run_dir.join(format!(".{}.tmp", BUILD_INFO_FILE));
+
+        let content =
+            serde_json::to_string_pretty(self).context("Failed to serialize build info")?;
+        fs::write(&temp_path, content)
+            .await
+            .context("Failed to write build info")?;
+        fs::rename(&temp_path, &path)
+            .await
+            .context("Failed to replace build info")?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,404 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs;
+
+/// Name of the optional per-project configuration file
+pub const CONFIG_FILE: &str = ".pumpkin.toml";
+
+/// Project configuration read from `.pumpkin.toml`
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Pumpkin repository cloned when `--repo-url` is not given
+    pub repo_url: Option<String>,
+    /// Pumpkin branch, tag or commit checked out when `--ref` is not given
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+    /// Port the server listens on when `--port` is not given
+    pub port: Option<u16>,
+    /// World directory inside `.run`, removed by `--fresh-worlds`
+    pub world_dir: Option<PathBuf>,
+    /// World seed written to `server.properties` before launch
+    pub seed: Option<String>,
+    /// Keys merged into `server.properties` before launch, keeping other lines
+    pub server_properties: BTreeMap<String, toml::Value>,
+    /// EULA file written by `init`, relative to `.run`
+    pub eula_path: Option<PathBuf>,
+    /// Content of the EULA file written by `init`
+    pub eula_content: Option<String>,
+    /// Paths inside `.run` kept by `--clean-run-before`, besides the world and EULA
+    pub persist: Vec<PathBuf>,
+    /// Command (program and arguments) that tells a running server to reload plugins
+    pub reload_command: Option<Vec<String>>,
+    /// Additional server run directories that receive a copy of the plugin
+    pub instances: Vec<PathBuf>,
+    /// Command (program and arguments) that builds the plugin, e.g. `["cross", "build"]`
+    pub build_command: Option<Vec<String>>,
+    /// Keep one Pumpkin checkout per ref under `.pumpkin-cache/<ref>/` instead of
+    /// re-checking out `Pumpkin/` whenever `--ref` changes
+    pub ref_cache: bool,
+    /// Retry a failed clone over SSH for an HTTPS `repo_url`, or over HTTPS for an SSH one
+    pub clone_fallback: bool,
+    /// Local bare mirror of the Pumpkin repository that clones borrow objects from
+    pub mirror_dir: Option<PathBuf>,
+    /// Project directories copied into `.run` before launch
+    pub assets: Vec<AssetConfig>,
+    /// Filename (without extension) the plugin is copied to in `plugins/`,
+    /// replacing the one derived from the crate name
+    pub plugin_out_name: Option<String>,
+    /// Native libraries the plugin needs at runtime, copied next to it in `plugins/`
+    pub runtime_libs: Vec<PathBuf>,
+    /// Linker used for plugin builds through `-fuse-ld`, e.g. `mold` or `lld`
+    pub linker: Option<String>,
+    /// `RUSTC_WRAPPER` (e.g. `sccache`) for the plugin and Pumpkin builds; an
+    /// exported `RUSTC_WRAPPER` takes precedence
+    pub rustc_wrapper: Option<String>,
+    /// Download the latest Pumpkin release instead of building from source
+    pub prefer_release: bool,
+    /// Check crates.io (at most once a day) for a newer cargo-pumpkin on startup
+    pub check_updates: bool,
+    /// Extra `docker run` arguments for `--docker`, placed before the image
+    pub docker_args: Vec<String>,
+    /// Remote server that `cargo pumpkin deploy` copies artifacts to
+    pub deploy: Option<DeployConfig>,
+    /// Cargo features enabled when building Pumpkin
+    pub pumpkin_features: Vec<String>,
+    /// Per-plugin build settings keyed by workspace member name
+    pub plugins: BTreeMap<String, PluginConfig>,
+    /// Subcommand (with arguments) run by a bare `cargo pumpkin`, e.g. `"update"`
+    pub default_command: Option<String>,
+    /// Restart behavior of `--watch`
+    pub watch: WatchConfig,
+    /// Platform-specific sections whose keys replace the base keys on that platform
+    #[serde(skip_serializing)]
+    pub os: OsOverrides,
+}
+
+/// `[deploy]` section of the config
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeployConfig {
+    /// Remote host, e.g. `user@example.com`
+    pub host: String,
+    /// Server directory on the remote host; plugins go to its `plugins/`
+    pub path: String,
+    /// Transfer tool to shell out to
+    #[serde(default)]
+    pub tool: DeployTool,
+    /// Also upload the Pumpkin server binary
+    #[serde(default)]
+    pub include_binary: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeployTool {
+    #[default]
+    Rsync,
+    Scp,
+}
+
+impl DeployTool {
+    pub fn program(self) -> &'static str {
+        match self {
+            DeployTool::Rsync => "rsync",
+            DeployTool::Scp => "scp",
+        }
+    }
+}
+
+/// `[plugins.<name>]` section of the config
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PluginConfig {
+    /// Cargo features enabled for this plugin when it is built
+    pub features: Vec<String>,
+}
+
+/// `[[assets]]` entry of the config
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AssetConfig {
+    /// Directory in the project whose files are copied
+    pub source: PathBuf,
+    /// Directory inside `.run` that receives them
+    pub dest: PathBuf,
+}
+
+/// `[watch]` section of the config
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WatchConfig {
+    /// A server exiting within this many seconds of starting counts as a crash
+    pub crash_window_secs: u64,
+    /// Crashes in a row after which restarts pause until the next file change
+    pub max_crashes: u32,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            crash_window_secs: 5,
+            max_crashes: 3,
+        }
+    }
+}
+
+/// `[os.windows]`, `[os.linux]` and `[os.macos]` sections of the config
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OsOverrides {
+    pub windows: Option<Box<Config>>,
+    pub linux: Option<Box<Config>>,
+    pub macos: Option<Box<Config>>,
+}
+
+impl Config {
+    pub async fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("Failed to read {}", CONFIG_FILE))?;
+
+        // Parse the file as written first so errors point at the right line
+        let config: Config = toml::from_str(&content).map_err(|err| {
+            anyhow::anyhow!("{}\n\n{}", describe_error(&content, &err), err)
+                .context(format!("Invalid {}", CONFIG_FILE))
+        })?;
+
+        let mut table: toml::Table = content
+            .parse()
+            .with_context(|| format!("Failed to parse {}", CONFIG_FILE))?;
+        let overrides = match table.remove("os") {
+            Some(toml::Value::Table(mut os)) => os.remove(std::env::consts::OS),
+            _ => None,
+        };
+        let Some(toml::Value::Table(overrides)) = overrides else {
+            return Ok(config);
+        };
+
+        table.extend(overrides);
+        toml::Value::Table(table)
+            .try_into()
+            .with_context(|| format!("Invalid [os.{}] section", std::env::consts::OS))
+    }
+
+    pub fn world_dir(&self) -> Result<PathBuf> {
+        let world_dir = self
+            .world_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("world"));
+        ensure_relative(&world_dir, "world_dir")?;
+        Ok(world_dir)
+    }
+
+    pub fn eula_path(&self) -> Result<PathBuf> {
+        let eula_path = self
+            .eula_path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("eula.txt"));
+        ensure_relative(&eula_path, "eula_path")?;
+        Ok(eula_path)
+    }
+
+    pub fn plugin_out_name(&self) -> Result<Option<&str>> {
+        let Some(name) = self.plugin_out_name.as_deref() else {
+            return Ok(None);
+        };
+
+        let safe = !name.is_empty()
+            && !name.starts_with('.')
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !safe {
+            anyhow::bail!(
+                "`plugin_out_name` must be a plain filename of letters, digits, `-`, `_` and `.`: {:?}",
+                name
+            );
+        }
+        Ok(Some(name))
+    }
+
+    pub fn eula_content(&self) -> &str {
+        self.eula_content.as_deref().unwrap_or("eula=true")
+    }
+}
+
+/// Commented `.pumpkin.toml` written by `init --interactive`
+pub fn scaffold(repo_url: &str, git_ref: Option<&str>, port: u16, prefer_release: bool) -> String {
+    // Quoted by the TOML serializer, Rust `{:?}` escapes are not valid TOML
+    let toml_string = |value: &str| toml::Value::String(value.to_string()).to_string();
+    let git_ref = match git_ref {
+        Some(git_ref) => format!("ref = {}", toml_string(git_ref)),
+        None => "# ref = \"master\"".to_string(),
+    };
+
+    format!(
+        "# cargo-pumpkin configuration, see `cargo pumpkin config` for all effective settings
+
+# Pumpkin repository to clone, and the branch, tag or commit to check out
+repo_url = {}
+{}
+
+# Port the server listens on
+port = {}
+
+# Download the latest Pumpkin release instead of building from source
+prefer_release = {}
+
+# World directory inside .run, removed by --fresh-worlds
+# world_dir = \"world\"
+
+# Seed written to server.properties so regenerated worlds are reproducible
+# seed = \"12345\"
+",
+        toml_string(repo_url),
+        git_ref,
+        port,
+        prefer_release
+    )
+}
+
+/// Reject paths that could escape the directory they are joined onto
+pub fn ensure_relative(path: &Path, key: &str) -> Result<()> {
+    let escapes = path
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+    if escapes || path.as_os_str().is_empty() {
+        anyhow::bail!(
+            "`{}` must be a relative path inside the run directory: {}",
+            key,
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+/// One-line summary of a config error, suggesting the closest key for typos
+fn describe_error(content: &str, err: &toml::de::Error) -> String {
+    let line = err
+        .span()
+        .map(|span| content[..span.start].matches('\n').count() + 1);
+    let location = line
+        .map(|line| format!(" at line {}", line))
+        .unwrap_or_default();
+
+    let message = err.message();
+    let Some(key) = message
+        .strip_prefix("unknown field `")
+        .and_then(|rest| rest.split('`').next())
+    else {
+        return format!("{}{}", message.trim(), location);
+    };
+
+    // serde lists the accepted keys as "expected one of `a`, `b`, ..."
+    let suggestion = message
+        .split_once("expected")
+        .map(|(_, expected)| expected.split('`').skip(1).step_by(2))
+        .and_then(|known| {
+            known
+                .map(|candidate| (edit_distance(key, candidate), candidate))
+                .filter(|(distance, _)| *distance <= key.len().max(3) / 3 + 1)
+                .min()
+        })
+        .map(|(_, candidate)| format!(", did you mean `{}`?", candidate))
+        .unwrap_or_default();
+
+    format!("unknown key `{}`{}{}", key, location, suggestion)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_for(content: &str) -> String {
+        let err = toml::from_str::<Config>(content).unwrap_err();
+        describe_error(content, &err)
+    }
+
+    #[test]
+    fn edit_distance_counts_single_edits() {
+        assert_eq!(edit_distance("port", "port"), 0);
+        assert_eq!(edit_distance("prot", "port"), 2);
+        assert_eq!(edit_distance("seeds", "seed"), 1);
+        assert_eq!(edit_distance("", "seed"), 4);
+        assert_eq!(edit_distance("wrld_dir", "world_dir"), 1);
+    }
+
+    #[test]
+    fn describe_error_suggests_the_closest_key() {
+        assert_eq!(
+            error_for("port = 25565\nwrld_dir = \"w\"\n"),
+            "unknown key `wrld_dir` at line 2, did you mean `world_dir`?"
+        );
+    }
+
+    #[test]
+    fn describe_error_skips_distant_suggestions() {
+        assert_eq!(
+            error_for("completely_unrelated = 1\n"),
+            "unknown key `completely_unrelated` at line 1"
+        );
+    }
+
+    #[test]
+    fn describe_error_reports_other_errors_with_their_line() {
+        let description = error_for("\nport = \"not a number\"\n");
+        assert!(description.ends_with(" at line 2"), "{}", description);
+        assert!(!description.contains("unknown key"), "{}", description);
+    }
+
+    #[test]
+    fn scaffold_round_trips_through_the_loader() {
+        let content = scaffold(
+            "https://example.com/Pümpkin \"fork\"\u{1b}.git",
+            Some("feature/ü"),
+            25566,
+            true,
+        );
+        let config: Config = toml::from_str(&content).unwrap();
+
+        assert_eq!(
+            config.repo_url.as_deref(),
+            Some("https://example.com/Pümpkin \"fork\"\u{1b}.git")
+        );
+        assert_eq!(config.git_ref.as_deref(), Some("feature/ü"));
+        assert_eq!(config.port, Some(25566));
+        assert!(config.prefer_release);
+    }
+
+    #[test]
+    fn scaffold_without_ref_leaves_it_commented() {
+        let content = scaffold(
+            "https://github.com/Pumpkin-MC/Pumpkin.git",
+            None,
+            25565,
+            false,
+        );
+        let config: Config = toml::from_str(&content).unwrap();
+        assert_eq!(config.git_ref, None);
+    }
+}
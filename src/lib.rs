@@ -0,0 +1,22 @@
+//! Library behind the `cargo pumpkin` subcommand.
+//!
+//! [`PumpkinRunner`] clones and builds Pumpkin, builds the plugin in the
+//! project directory, copies both into `.run` and starts the server. The CLI
+//! is a thin wrapper that maps its flags onto [`RunnerOptions`].
+
+mod archive;
+mod assets;
+mod build_info;
+mod config;
+mod log;
+mod metadata;
+mod notify;
+mod release;
+mod runner;
+mod symbols;
+mod update_check;
+
+pub use runner::{
+    ArtifactKind, ConfigFormat, DEFAULT_PORT, DEFAULT_REPO_URL, PanicStrategy, PumpkinRunner,
+    RunnerOptions,
+};
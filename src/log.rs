@@ -0,0 +1,262 @@
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Append-only log file that is rotated to `<path>.1`, `<path>.2`, ... once it
+/// grows past `max_size`
+pub struct RotatingLog {
+    path: PathBuf,
+    max_size: Option<u64>,
+    rotations: usize,
+    file: File,
+    size: u64,
+}
+
+impl RotatingLog {
+    pub fn open(path: &Path, max_size: Option<u64>, rotations: usize) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open log file {}", path.display()))?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_size,
+            rotations,
+            file,
+            size,
+        })
+    }
+
+    pub fn write_line(&mut self, line: &str) -> Result<()> {
+        let len = line.len() as u64 + 1;
+        if self
+            .max_size
+            .is_some_and(|max| self.size > 0 && self.size + len > max)
+        {
+            self.rotate()?;
+        }
+
+        writeln!(self.file, "{}", line).context("Failed to write log file")?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> Result<()> {
+        let rotated = |n: usize| PathBuf::from(format!("{}.{}", self.path.display(), n));
+
+        if self.rotations == 0 {
+            self.file
+                .set_len(0)
+                .context("Failed to truncate log file")?;
+        } else {
+            let _ = std::fs::remove_file(rotated(self.rotations));
+            for n in (1..self.rotations).rev() {
+                let _ = std::fs::rename(rotated(n), rotated(n + 1));
+            }
+            std::fs::rename(&self.path, rotated(1)).context("Failed to rotate log file")?;
+            *self = Self::open(&self.path, self.max_size, self.rotations)?;
+        }
+
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Copy each line of `reader` to `echo` and every log on a background thread
+pub fn tee<R, W>(reader: R, mut echo: W, logs: Vec<Arc<Mutex<RotatingLog>>>) -> JoinHandle<()>
+where
+    R: Read + Send + 'static,
+    W: Write + Send + 'static,
+{
+    std::thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else { break };
+            let _ = writeln!(echo, "{}", line);
+            for log in &logs {
+                if let Ok(mut log) = log.lock() {
+                    let _ = log.write_line(&line);
+                }
+            }
+        }
+    })
+}
+
+/// Path for a new session log in `dir`, named after the current UTC time
+pub fn session_log_path(dir: &Path) -> PathBuf {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    dir.join(format!("{}.log", utc_timestamp(secs)))
+}
+
+/// Remove all but the newest `keep` session logs in `dir`.
+///
+/// Only files named like [`session_log_path`] are touched, so other logs in
+/// the directory survive.
+pub fn prune_session_logs(dir: &Path, keep: usize) -> Result<()> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(());
+    };
+
+    let mut sessions: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "log")
+                && path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .is_some_and(is_timestamp)
+        })
+        .collect();
+    // Timestamps sort chronologically as strings
+    sessions.sort();
+
+    let excess = sessions.len().saturating_sub(keep);
+    for path in &sessions[..excess] {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove old session log {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// `YYYYMMDD-HHMMSS` for a Unix time in seconds
+fn utc_timestamp(secs: u64) -> String {
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let days = secs / 86400;
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z % 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+fn is_timestamp(name: &str) -> bool {
+    name.len() == 15
+        && name
+            .char_indices()
+            .all(|(i, c)| if i == 8 { c == '-' } else { c.is_ascii_digit() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("cargo-pumpkin-log-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn utc_timestamp_formats_civil_time() {
+        assert_eq!(utc_timestamp(0), "19700101-000000");
+        assert_eq!(utc_timestamp(951_782_400), "20000229-000000");
+        assert_eq!(utc_timestamp(1_709_251_199), "20240229-235959");
+        assert_eq!(utc_timestamp(1_735_689_600), "20250101-000000");
+    }
+
+    #[test]
+    fn is_timestamp_matches_session_names_only() {
+        assert!(is_timestamp("20250101-000000"));
+        assert!(!is_timestamp("20250101_000000"));
+        assert!(!is_timestamp("server"));
+        assert!(!is_timestamp("20250101-0000000"));
+    }
+
+    #[test]
+    fn rotating_log_rotates_past_the_size_limit() {
+        let dir = scratch_dir("rotate");
+        let path = dir.join("server.log");
+        let rotated = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+
+        // Each line takes 5 bytes with its newline, so two fill the 10 byte limit
+        let mut log = RotatingLog::open(&path, Some(10), 2).unwrap();
+        log.write_line("aaaa").unwrap();
+        log.write_line("bbbb").unwrap();
+        assert!(!rotated(1).exists());
+
+        log.write_line("cccc").unwrap();
+        assert_eq!(std::fs::read_to_string(rotated(1)).unwrap(), "aaaa\nbbbb\n");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "cccc\n");
+
+        log.write_line("dd").unwrap();
+        log.write_line("eeeeeeee").unwrap();
+        log.write_line("ffffffff").unwrap();
+        assert_eq!(std::fs::read_to_string(rotated(2)).unwrap(), "cccc\ndd\n");
+        assert_eq!(std::fs::read_to_string(rotated(1)).unwrap(), "eeeeeeee\n");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "ffffffff\n");
+        assert!(!rotated(3).exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rotating_log_keeps_oversized_lines_and_truncates_without_rotations() {
+        let dir = scratch_dir("truncate");
+        let path = dir.join("server.log");
+
+        let mut log = RotatingLog::open(&path, Some(4), 0).unwrap();
+        log.write_line("longer than the limit").unwrap();
+        log.write_line("next").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "next\n");
+        assert!(!dir.join("server.log.1").exists());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn prune_session_logs_keeps_the_newest() {
+        let dir = scratch_dir("prune");
+        for name in [
+            "20250101-000000.log",
+            "20250102-000000.log",
+            "20250103-000000.log",
+            "server.log",
+        ] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        prune_session_logs(&dir, 2).unwrap();
+        let mut left: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        left.sort();
+        assert_eq!(
+            left,
+            ["20250102-000000.log", "20250103-000000.log", "server.log"]
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
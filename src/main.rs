@@ -1,9 +1,9 @@
-use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
-use colored::*;
+use anyhow::Result;
+use cargo_pumpkin::{ArtifactKind, ConfigFormat, PanicStrategy, PumpkinRunner, RunnerOptions};
+use clap::builder::BoolishValueParser;
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+use std::io::IsTerminal;
 use std::path::PathBuf;
-use std::process::{Command, Stdio};
-use tokio::fs;
 
 #[derive(Parser)]
 #[command(name = "cargo")]
@@ -25,361 +25,587 @@ struct PumpkinArgs {
     /// Skip building the current project
     #[arg(long)]
     skip_self_build: bool,
-}
 
-#[derive(Subcommand)]
-enum Commands {
-    /// Initialize and setup the environment
-    Init,
-    /// Build and run the server
-    Run,
-    /// Clean the .run directory
-    Clean,
-}
+    /// Which library output of the plugin to copy into the plugins directory
+    #[arg(long, value_enum, default_value_t = ArtifactKind::Cdylib)]
+    artifact_kind: ArtifactKind,
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let CargoCli::Pumpkin(args) = CargoCli::parse();
+    /// Set an environment variable for the server (KEY=VALUE, repeatable)
+    #[arg(long = "env", value_name = "KEY=VALUE", value_parser = parse_env_var)]
+    env: Vec<(String, String)>,
 
-    let pumpkin_runner = PumpkinRunner::new().await?;
+    /// Load server environment variables from a dotenv file (defaults to `.env` if present)
+    #[arg(long, value_name = "PATH")]
+    env_file: Option<PathBuf>,
 
-    match args.command.unwrap_or(Commands::Run) {
-        Commands::Init => pumpkin_runner.init(args.force).await,
-        Commands::Run => pumpkin_runner.run(args.force, args.skip_self_build).await,
-        Commands::Clean => pumpkin_runner.clean().await,
-    }
-}
+    /// Delete the world directory before starting the server
+    #[arg(long)]
+    fresh_worlds: bool,
 
-struct PumpkinRunner {
-    current_dir: PathBuf,
-    run_dir: PathBuf,
-    pumpkin_dir: PathBuf,
-}
+    /// Port the server listens on (default: 25565)
+    #[arg(long)]
+    port: Option<u16>,
 
-impl PumpkinRunner {
-    async fn new() -> Result<Self> {
-        let current_dir = std::env::current_dir().context("Failed to get current directory")?;
+    /// Show API-relevant files changed by a Pumpkin update
+    #[arg(long)]
+    show_changes: bool,
 
-        let run_dir = current_dir.join(".run");
-        let pumpkin_dir = current_dir.join("Pumpkin");
+    /// Build Pumpkin with one job and fewer codegen units to avoid running out of memory
+    #[arg(long)]
+    low_memory: bool,
 
-        Ok(Self {
-            current_dir,
-            run_dir,
-            pumpkin_dir,
-        })
-    }
+    /// Path to the plugin's Cargo.toml (defaults to the current directory)
+    #[arg(long, value_name = "PATH")]
+    manifest_path: Option<PathBuf>,
 
-    async fn init(&self, force: bool) -> Result<()> {
-        println!("{}", "Initializing Pumpkin environment...".yellow().bold());
+    /// Also copy cdylib dependencies of the plugin into the plugins directory
+    #[arg(long)]
+    copy_dylib_deps: bool,
 
-        fs::create_dir_all(&self.run_dir)
-            .await
-            .context("Failed to create .run directory")?;
+    /// Pumpkin repository to clone (default: the official Pumpkin repository)
+    #[arg(long, env = "PUMPKIN_REPO_URL")]
+    repo_url: Option<String>,
 
-        self.setup_pumpkin_repo(force).await?;
+    /// Pumpkin branch, tag or commit to check out
+    #[arg(long = "ref", env = "PUMPKIN_REF", value_name = "REF")]
+    git_ref: Option<String>,
 
-        println!("{}", "Initialization complete!".green().bold());
-        Ok(())
-    }
+    /// Print the contents of the run directory after copying artifacts
+    #[arg(long)]
+    post_copy_verify: bool,
 
-    async fn run(&self, force: bool, skip_self_build: bool) -> Result<()> {
-        println!("{}", "Starting Pumpkin runner...".yellow().bold());
+    /// Fail if the server does not accept connections within this many seconds
+    #[arg(long, value_name = "SECS")]
+    timeout_server_start: Option<u64>,
 
-        fs::create_dir_all(&self.run_dir)
-            .await
-            .context("Failed to create .run directory")?;
+    /// Keep building other workspace plugins when one fails and copy the ones that built
+    #[arg(long)]
+    keep_going: bool,
 
-        if force || !self.pumpkin_dir.exists() {
-            self.setup_pumpkin_repo(force).await?;
-        }
+    /// Strip debug symbols from the copied plugin (ignored on Windows)
+    #[arg(long)]
+    strip: bool,
 
-        if !skip_self_build {
-            self.build_current_project().await?;
-        }
+    /// Download this Pumpkin release (or `latest`) instead of building from source
+    #[arg(long, value_name = "TAG")]
+    pumpkin_release: Option<String>,
 
-        self.build_pumpkin_server().await?;
+    /// Also copy every shared library in this directory into the plugins directory
+    #[arg(long, value_name = "DIR")]
+    plugins_from: Option<PathBuf>,
 
-        self.copy_artifacts().await?;
+    /// Skip building the plugin and Pumpkin, copying and running the existing artifacts
+    #[arg(long)]
+    no_build: bool,
 
-        self.run_server().await?;
+    /// Build the plugin and Pumpkin in parallel, streaming output tagged [plugin]/[server]
+    #[arg(long)]
+    tail_build: bool,
 
-        Ok(())
-    }
+    /// Suppress streamed build output and progress details
+    #[arg(short, long)]
+    quiet: bool,
 
-    async fn clean(&self) -> Result<()> {
-        println!("{}", "Cleaning .run directory...".yellow().bold());
+    /// World seed written to server.properties (overrides the config seed)
+    #[arg(long)]
+    seed: Option<String>,
 
-        if self.run_dir.exists() {
-            fs::remove_dir_all(&self.run_dir)
-                .await
-                .context("Failed to remove .run directory")?;
-        }
+    /// Unix permission mode for the server binary, e.g. 750 (default: 755 if not already executable)
+    #[arg(long, value_name = "MODE", value_parser = parse_mode)]
+    binary_mode: Option<u32>,
 
-        println!("{}", "Clean complete!".green().bold());
-        Ok(())
-    }
+    /// Stash local changes in the Pumpkin checkout before pulling and restore them afterwards
+    #[arg(long)]
+    stash: bool,
 
-    async fn setup_pumpkin_repo(&self, force: bool) -> Result<()> {
-        if self.pumpkin_dir.exists() {
-            if force {
-                println!("{}", "Force rebuilding Pumpkin...".blue());
-                fs::remove_dir_all(&self.pumpkin_dir)
-                    .await
-                    .context("Failed to remove existing Pumpkin directory")?;
-            } else {
-                println!(
-                    "{}",
-                    "Pumpkin repository already exists, pulling latest changes...".blue()
-                );
-                self.git_pull().await?;
-                return Ok(());
-            }
-        }
+    /// Print the detected plugins and their artifact paths without building or running
+    #[arg(long)]
+    list_plugins: bool,
 
-        println!("{}", "Cloning Pumpkin repository...".blue());
+    /// Print the resolved project, .run, Pumpkin, plugin and server binary paths
+    /// as KEY=VALUE lines and exit
+    #[arg(long, conflicts_with = "list_plugins")]
+    print_paths: bool,
 
-        let output = Command::new("git")
-            .args(&["clone", "https://github.com/Pumpkin-MC/Pumpkin.git"])
-            .current_dir(&self.current_dir)
-            .output()
-            .context("Failed to execute git clone")?;
+    /// Proxy URL for git and release downloads (overrides HTTP_PROXY/HTTPS_PROXY)
+    #[arg(long, value_name = "URL")]
+    proxy: Option<String>,
 
-        if !output.status.success() {
-            anyhow::bail!(
-                "Git clone failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
+    /// Rebuild the plugin and restart the server whenever its sources change
+    #[arg(short, long)]
+    watch: bool,
+    /// Clear the terminal and print a timestamp before each watch rebuild (skipped with --quiet)
+    /// Clear the terminal and print a timestamp before each watch rebuild
+    #[arg(long, requires = "watch")]
+    watch_clear: bool,
 
-        println!("{}", "Pumpkin repository cloned successfully!".green());
-        Ok(())
-    }
+    /// Copy this file to .run/server.properties before launch
+    #[arg(long, value_name = "PATH")]
+    server_properties: Option<PathBuf>,
 
-    async fn git_pull(&self) -> Result<()> {
-        let output = Command::new("git")
-            .args(&["pull"])
-            .current_dir(&self.pumpkin_dir)
-            .output()
-            .context("Failed to execute git pull")?;
-
-        if !output.status.success() {
-            println!(
-                "{}",
-                "Git pull failed, continuing with existing version...".yellow()
-            );
-        } else {
-            println!("{}", "Pumpkin repository updated!".green());
-        }
+    /// Replace an existing .run/server.properties that differs from --server-properties
+    #[arg(long, requires = "server_properties")]
+    overwrite_server_properties: bool,
 
-        Ok(())
-    }
+    /// Symlink the plugin into .run/plugins instead of copying it (falls back to copying on Windows without symlink privilege)
+    #[arg(long, conflicts_with = "strip")]
+    symlink_plugin: bool,
 
-    async fn build_current_project(&self) -> Result<()> {
-        println!("{}", "Building current project...".blue());
+    /// Start the server with an empty environment except PATH, the env file and --env
+    #[arg(long)]
+    env_clear: bool,
 
-        let mut args = vec!["build"];
+    /// Start the server in the background, logging to .run/server.log
+    #[arg(long, conflicts_with = "watch")]
+    detach: bool,
 
-        if cfg!(target_os = "windows") {
-            args.push("--release");
-            println!(
-                "{}",
-                "  Windows detected: Using release build for plugin compatibility".yellow()
-            );
-        }
+    /// Append a heartbeat line to the server log every SECS seconds while the detached server runs
+    #[arg(long, value_name = "SECS", requires = "detach")]
+    heartbeat: Option<u64>,
 
-        let output = Command::new("cargo")
-            .args(&args)
-            .current_dir(&self.current_dir)
-            .output()
-            .context("Failed to build current project")?;
-
-        if !output.status.success() {
-            anyhow::bail!(
-                "Current plugin build failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
+    /// Restore the previous server binary and plugins in .run if the new ones fail to start or
+    /// the server exits with an error within `watch.crash_window_secs` (or `--server-timeout`)
+    #[arg(long)]
+    rollback_on_fail: bool,
 
-        println!("{}", "Plugin built successfully!".green());
-        Ok(())
-    }
+    /// Binary built by the Pumpkin workspace to run (default: pumpkin), copied to .run/pumpkin
+    #[arg(long, value_name = "NAME")]
+    server_bin_name: Option<String>,
+
+    /// Fail the plugin build on any compiler warning (RUSTFLAGS=-Dwarnings, not applied to Pumpkin)
+    #[arg(long)]
+    fail_on_warnings: bool,
 
-    async fn build_pumpkin_server(&self) -> Result<()> {
-        println!("{}", "Building Pumpkin server...".blue());
+    /// Delete download and checksum markers in .run so artifacts are fetched again (keeps worlds and configs)
+    #[arg(long)]
+    purge_cache: bool,
 
-        let output = Command::new("cargo")
-            .args(&["build"])
-            .current_dir(&self.pumpkin_dir)
-            .output()
-            .context("Failed to build Pumpkin server")?;
+    /// Remove everything in .run except the world, EULA and `persist` paths before copying artifacts
+    #[arg(long)]
+    clean_run_before: bool,
+
+    /// Also write the server output to this file
+    #[arg(long, value_name = "PATH", conflicts_with = "detach")]
+    log_file: Option<PathBuf>,
+
+    /// Rotate the log file to .1, .2, ... once it grows past this many bytes
+    #[arg(long, value_name = "BYTES", requires = "log_file")]
+    max_log_size: Option<u64>,
+
+    /// Number of rotated log files to keep
+    #[arg(long, value_name = "N", default_value_t = 3, requires = "max_log_size")]
+    log_rotations: usize,
+
+    /// Run the tool on a single-threaded async runtime (the work is mostly
+    /// subprocesses, so this only trims the tool's own footprint)
+    #[arg(
+        long,
+        env = "PUMPKIN_SINGLE_THREAD",
+        action = ArgAction::SetTrue,
+        value_parser = BoolishValueParser::new()
+    )]
+    single_thread: bool,
+
+    /// Warn when the built plugin does not export the Pumpkin entrypoint symbols
+    #[arg(long)]
+    verify_symbols: bool,
 
-        if !output.status.success() {
-            anyhow::bail!(
-                "Pumpkin server build failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-        }
+    /// Cargo `--config` override for the plugin and Pumpkin builds (repeatable)
+    #[arg(long = "cargo-config", value_name = "KEY=VALUE", value_parser = parse_cargo_config)]
+    cargo_config: Vec<String>,
 
-        println!("{}", "Pumpkin server built successfully!".green());
-        Ok(())
-    }
+    /// Soak test: start the server N times in a row, logging each exit (0 repeats until interrupted)
+    #[arg(long, value_name = "N", conflicts_with_all = ["watch", "detach"])]
+    run_count: Option<u32>,
 
-    async fn copy_artifacts(&self) -> Result<()> {
-        println!("{}", "Copying artifacts to .run directory...".blue());
+    /// Stop the --run-count loop at the first run that exits unsuccessfully
+    #[arg(long, requires = "run_count")]
+    stop_on_failure: bool,
 
-        let pumpkin_binary = self.pumpkin_dir.join("target/debug/pumpkin");
-        if pumpkin_binary.exists() {
-            let dest = self.run_dir.join("pumpkin");
-            fs::copy(&pumpkin_binary, &dest)
-                .await
-                .context("Failed to copy Pumpkin binary")?;
-            println!("{}", "  Copied Pumpkin server binary".green());
-        }
+    /// Show a desktop notification when the Pumpkin build finishes or the server crashes
+    #[arg(long)]
+    notify: bool,
 
-        let project_name = self.get_project_name().await?;
+    /// Run the server inside a Docker container from IMAGE, mounting .run and forwarding the port
+    #[arg(long, value_name = "IMAGE")]
+    docker: Option<String>,
 
-        if let Some(name) = project_name {
-            self.copy_plugin_artifact(&name).await?;
-        }
+    /// Let --force delete a Pumpkin checkout that has uncommitted changes
+    #[arg(long)]
+    allow_dirty: bool,
 
-        println!("{}", "Artifacts copied successfully!".green());
-        Ok(())
-    }
+    /// Comma-separated cargo features for the Pumpkin build (overrides pumpkin_features in .pumpkin.toml)
+    #[arg(long, value_name = "FEATURES")]
+    pumpkin_features: Option<String>,
 
-    async fn get_project_name(&self) -> Result<Option<String>> {
-        let cargo_toml_path = self.current_dir.join("Cargo.toml");
-        if !cargo_toml_path.exists() {
-            return Ok(None);
-        }
+    /// Describe each step in a sentence before running it (ignored with --quiet)
+    #[arg(long)]
+    explain: bool,
+
+    /// Also place the built plugins in DIR for distribution, independent of .run
+    #[arg(long, value_name = "DIR")]
+    out_dir: Option<PathBuf>,
+
+    /// Also place the server binary in the --out-dir directory
+    #[arg(long, requires = "out_dir")]
+    out_dir_server: bool,
+
+    /// How long sources must stay unchanged before --watch rebuilds, in milliseconds
+    #[arg(long, value_name = "MS", default_value_t = 500, requires = "watch")]
+    watch_debounce: u64,
+
+    /// Also write each server session's output to .run/logs/<timestamp>.log
+    #[arg(long, conflicts_with = "detach")]
+    keep_run_logs: bool,
+
+    /// Number of recent session logs kept by --keep-run-logs
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 20,
+        requires = "keep_run_logs"
+    )]
+    run_log_limit: usize,
+
+    /// Only copy asset files changed since the last run and remove deleted ones (tracked in .run/.asset-manifest)
+    #[arg(long)]
+    since_last_run: bool,
 
-        let content = fs::read_to_string(&cargo_toml_path)
-            .await
-            .context("Failed to read Cargo.toml")?;
-
-        for line in content.lines() {
-            if line.trim().starts_with("name") && line.contains("=") {
-                let name = line
-                    .split('=')
-                    .nth(1)
-                    .unwrap_or("")
-                    .trim()
-                    .trim_matches('"')
-                    .trim_matches('\'');
-                return Ok(Some(name.to_string()));
-            }
+    /// Bind the server to this IP address (written to server.properties as server-ip)
+    #[arg(long, value_name = "IP")]
+    bind_address: Option<std::net::IpAddr>,
+
+    /// Panic strategy for the plugin build; with abort a plugin panic takes down the whole server
+    #[arg(long, value_enum, value_name = "STRATEGY")]
+    panic: Option<PanicStrategy>,
+
+    /// Smoke test: stop the server after SECS seconds, succeeding only if it stayed up that long
+    #[arg(long, value_name = "SECS", conflicts_with_all = ["watch", "detach", "run_count"])]
+    server_timeout: Option<u64>,
+
+    /// Copy up to N asset files at the same time
+    #[arg(long, value_name = "N", default_value_t = 16)]
+    asset_jobs: usize,
+
+    /// When to use colored output
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ColorChoice {
+    /// Color when writing to a capable terminal
+    Auto,
+    Always,
+    Never,
+}
+
+impl PumpkinArgs {
+    fn runner_options(&self) -> RunnerOptions {
+        RunnerOptions {
+            manifest_path: self.manifest_path.clone(),
+            artifact_kind: self.artifact_kind,
+            env: self.env.clone(),
+            env_file: self.env_file.clone(),
+            fresh_worlds: self.fresh_worlds,
+            port: self.port,
+            show_changes: self.show_changes,
+            low_memory: self.low_memory,
+            copy_dylib_deps: self.copy_dylib_deps,
+            repo_url: self.repo_url.clone(),
+            git_ref: self.git_ref.clone(),
+            post_copy_verify: self.post_copy_verify,
+            timeout_server_start: self.timeout_server_start,
+            keep_going: self.keep_going,
+            strip: self.strip,
+            pumpkin_release: self.pumpkin_release.clone(),
+            plugins_from: self.plugins_from.clone(),
+            no_build: self.no_build,
+            tail_build: self.tail_build,
+            quiet: self.quiet,
+            seed: self.seed.clone(),
+            binary_mode: self.binary_mode,
+            stash: self.stash,
+            proxy: self.proxy.clone(),
+            watch: self.watch,
+            watch_clear: self.watch_clear,
+            server_properties: self.server_properties.clone(),
+            overwrite_server_properties: self.overwrite_server_properties,
+            symlink_plugin: self.symlink_plugin,
+            env_clear: self.env_clear,
+            detach: self.detach,
+            heartbeat: self.heartbeat,
+            rollback_on_fail: self.rollback_on_fail,
+            server_bin_name: self.server_bin_name.clone(),
+            fail_on_warnings: self.fail_on_warnings,
+            purge_cache: self.purge_cache,
+            clean_run_before: self.clean_run_before,
+            log_file: self.log_file.clone(),
+            max_log_size: self.max_log_size,
+            log_rotations: self.log_rotations,
+            verify_symbols: self.verify_symbols,
+            cargo_config: self.cargo_config.clone(),
+            run_count: self.run_count,
+            stop_on_failure: self.stop_on_failure,
+            notify: self.notify,
+            docker: self.docker.clone(),
+            allow_dirty: self.allow_dirty,
+            pumpkin_features: self.pumpkin_features.clone(),
+            explain: self.explain,
+            out_dir: self.out_dir.clone(),
+            out_dir_server: self.out_dir_server,
+            watch_debounce: self.watch_debounce,
+            keep_run_logs: self.keep_run_logs,
+            run_log_limit: self.run_log_limit,
+            since_last_run: self.since_last_run,
+            bind_address: self.bind_address,
+            panic: self.panic,
+            server_timeout: self.server_timeout,
+            asset_jobs: self.asset_jobs,
         }
+    }
+}
+
+fn parse_env_var(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("invalid KEY=VALUE: no `=` found in `{}`", s)),
+    }
+}
 
-        Ok(None)
+fn parse_cargo_config(s: &str) -> Result<String, String> {
+    match s.split_once('=') {
+        Some((key, _)) if !key.trim().is_empty() => Ok(s.to_string()),
+        _ => Err(format!("invalid KEY=VALUE: no `=` found in `{}`", s)),
     }
+}
+
+fn parse_mode(s: &str) -> Result<u32, String> {
+    u32::from_str_radix(s.trim_start_matches("0o"), 8)
+        .ok()
+        .filter(|mode| *mode <= 0o7777)
+        .ok_or_else(|| format!("invalid octal mode `{}`", s))
+}
+
+/// Parser for the `default_command` config key
+#[derive(Parser)]
+#[command(name = "default_command", no_binary_name = true)]
+struct DefaultCommand {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+fn parse_default_command(command: &str) -> Result<Commands> {
+    DefaultCommand::try_parse_from(command.split_whitespace())
+        .map(|parsed| parsed.command)
+        .map_err(|err| anyhow::anyhow!("Invalid default_command `{}`:\n{}", command, err))
+}
 
-    async fn copy_plugin_artifact(&self, name: &str) -> Result<()> {
-        let build_dir = if cfg!(target_os = "windows") {
-            "release"
-        } else {
-            "debug"
-        };
-
-        let (lib_prefix, extension) = if cfg!(target_os = "windows") {
-            ("", ".dll")
-        } else if cfg!(target_os = "macos") {
-            ("lib", ".dylib")
-        } else {
-            ("lib", ".so")
-        };
-
-        let plugin_filename = format!("{}{}{}", lib_prefix, name, extension);
-        let mut plugin_path = self
-            .current_dir
-            .join(format!("target/{}/{}", build_dir, plugin_filename));
-
-        if !plugin_path.exists() {
-            let name_with_underscores = name.replace('-', "_");
-            let plugin_filename_alt =
-                format!("{}{}{}", lib_prefix, name_with_underscores, extension);
-            let plugin_path_alt = self
-                .current_dir
-                .join(format!("target/{}/{}", build_dir, plugin_filename_alt));
-
-            if plugin_path_alt.exists() {
-                plugin_path = plugin_path_alt;
+#[derive(Subcommand)]
+enum Commands {
+    /// Initialize and setup the environment
+    Init {
+        /// Only clone or update the Pumpkin repository
+        #[arg(long, conflicts_with = "dirs_only")]
+        repo_only: bool,
+
+        /// Only create the .run directory and EULA file
+        #[arg(long)]
+        dirs_only: bool,
+
+        /// Ask for the common settings and write a commented .pumpkin.toml first
+        #[arg(long)]
+        interactive: bool,
+
+        /// Replace an existing .pumpkin.toml with --interactive
+        #[arg(long, requires = "interactive")]
+        overwrite_config: bool,
+    },
+    /// Build and run the server
+    Run,
+    /// Clean the .run directory
+    Clean,
+    /// Show or update the Pumpkin commit pinned in .pumpkin-version
+    Pin {
+        /// Compare the pinned commit with the checked-out one (the default)
+        #[arg(long, conflicts_with = "update")]
+        status: bool,
+
+        /// Pin the currently checked-out Pumpkin commit
+        #[arg(long)]
+        update: bool,
+    },
+    /// Download the dependencies of the plugin and Pumpkin without building, e.g. to fill a CI cache
+    Fetch,
+    /// Build Pumpkin and the plugin into .run without starting the server
+    Build,
+    /// Copy already built Pumpkin and plugin artifacts into .run without building or running
+    Copy,
+    /// Build and copy artifacts, then upload them to the configured remote server
+    Deploy,
+    /// Rebuild and recopy the plugin, then ask the running server to reload it
+    Reload,
+    /// Run a cargo subcommand in the plugin with the tool's build settings
+    Cargo {
+        /// Cargo subcommand and its arguments, e.g. `test --lib`
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true, required = true)]
+        args: Vec<String>,
+    },
+    /// Start the server with the plugin, run the plugin's `cargo test` against it, then stop it
+    Test {
+        /// Arguments passed to `cargo test`, e.g. `--test integration`
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Type-check the plugin with the plugin build settings, without building Pumpkin
+    Check,
+    /// Build the plugin documentation with the plugin build settings
+    Doc {
+        /// Open the documentation in a browser afterwards
+        #[arg(long)]
+        open: bool,
+    },
+    /// Pack .run into a gzipped tarball, e.g. to cache build outputs in CI
+    Archive {
+        /// Archive file to write
+        path: PathBuf,
+
+        /// Only include the server binary, plugins and build metadata
+        #[arg(long)]
+        artifacts_only: bool,
+    },
+    /// Unpack an archive written by `archive` into .run
+    Restore {
+        /// Archive file to read
+        path: PathBuf,
+    },
+    /// Print the effective configuration from defaults, .pumpkin.toml, environment and flags
+    Config {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ConfigFormat::Toml)]
+        format: ConfigFormat,
+        /// Print `--env` values and `--proxy` credentials instead of masking them
+        #[arg(long)]
+        show_secrets: bool,
+    },
+    /// Log heartbeats for a detached server until it exits (started by --heartbeat)
+    #[command(hide = true)]
+    Heartbeat {
+        pid: u32,
+
+        #[arg(long, default_value_t = 60)]
+        interval: u64,
+    },
+    /// Pull the latest Pumpkin changes and show what changed
+    Update {
+        /// Show commits since this ref instead of since the previous HEAD
+        #[arg(long, value_name = "REF")]
+        since: Option<String>,
+
+        /// Maximum number of commits to show
+        #[arg(long, default_value_t = 20)]
+        max_commits: usize,
+    },
+}
+
+fn main() -> Result<()> {
+    let CargoCli::Pumpkin(args) = CargoCli::parse();
+
+    let mut runtime = if args.single_thread {
+        tokio::runtime::Builder::new_current_thread()
+    } else {
+        tokio::runtime::Builder::new_multi_thread()
+    };
+    runtime.enable_all().build()?.block_on(run(args))
+}
+
+async fn run(args: PumpkinArgs) -> Result<()> {
+    match args.color {
+        ColorChoice::Always => colored::control::set_override(true),
+        ColorChoice::Never => colored::control::set_override(false),
+        ColorChoice::Auto => {
+            let dumb_term = std::env::var("TERM").is_ok_and(|term| term == "dumb");
+            if dumb_term || !std::io::stdout().is_terminal() {
+                colored::control::set_override(false);
             }
         }
+    }
 
-        if plugin_path.exists() {
-            let plugins_dir = self.run_dir.join("plugins");
-            fs::create_dir_all(&plugins_dir)
-                .await
-                .context("Failed to create plugins directory")?;
-
-            let plugin_filename = plugin_path
-                .file_name()
-                .unwrap()
-                .to_string_lossy()
-                .to_string();
-            let dest = plugins_dir.join(&plugin_filename);
-            fs::copy(&plugin_path, &dest)
-                .await
-                .context("Failed to copy plugin file")?;
-            println!(
-                "{}",
-                format!("  Copied plugin {} to plugins/", plugin_filename).green()
-            );
-        } else {
-            println!(
-                "{}",
-                format!(
-                    "  Plugin {} not found at {}",
-                    plugin_filename,
-                    plugin_path.display()
-                )
-                .yellow()
-            );
-        }
+    let options = args.runner_options();
+    let pumpkin_runner = PumpkinRunner::new(options.clone()).await?;
 
-        Ok(())
+    if args.list_plugins {
+        return pumpkin_runner.list_plugins().await;
     }
 
-    async fn run_server(&self) -> Result<()> {
-        println!("{}", "Starting Pumpkin server...".yellow().bold());
+    if args.print_paths {
+        return pumpkin_runner.print_paths().await;
+    }
 
-        let pumpkin_binary = self.run_dir.join("pumpkin");
+    let command = match (args.command, pumpkin_runner.default_command()) {
+        (Some(command), _) => command,
+        (None, Some(default)) => parse_default_command(default)?,
+        (None, None) => Commands::Run,
+    };
+
+    // Keep machine-readable output and the detached watchdog free of the hint
+    if !matches!(
+        command,
+        Commands::Config { .. } | Commands::Heartbeat { .. }
+    ) {
+        pumpkin_runner.check_for_updates().await;
+    }
 
-        if !pumpkin_binary.exists() {
-            anyhow::bail!("Pumpkin binary not found in .run directory");
+    match command {
+        Commands::Init {
+            repo_only,
+            dirs_only,
+            interactive,
+            overwrite_config,
+        } => {
+            let pumpkin_runner = if interactive {
+                pumpkin_runner.init_config_interactive(overwrite_config)?;
+                // Pick up the settings that were just written
+                PumpkinRunner::new(options).await?
+            } else {
+                pumpkin_runner
+            };
+            pumpkin_runner
+                .init(args.force, !dirs_only, !repo_only)
+                .await
         }
-
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = fs::metadata(&pumpkin_binary).await?.permissions();
-            perms.set_mode(0o755);
-            fs::set_permissions(&pumpkin_binary, perms).await?;
+        Commands::Run => pumpkin_runner.run(args.force, args.skip_self_build).await,
+        Commands::Clean => pumpkin_runner.clean().await,
+        Commands::Pin { update, .. } => pumpkin_runner.pin(update).await,
+        Commands::Fetch => pumpkin_runner.fetch().await,
+        Commands::Build => pumpkin_runner.build(args.force, args.skip_self_build).await,
+        Commands::Copy => pumpkin_runner.copy().await,
+        Commands::Deploy => {
+            pumpkin_runner
+                .deploy(args.force, args.skip_self_build)
+                .await
         }
-
-        println!(
-            "{}",
-            "Server is starting... (Press Ctrl+C to stop)"
-                .green()
-                .bold()
-        );
-
-        let mut child = Command::new(&pumpkin_binary)
-            .current_dir(&self.run_dir)
-            .stdin(Stdio::inherit())
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .spawn()
-            .context("Failed to start Pumpkin server")?;
-
-        let status = child.wait().context("Failed to wait for server process")?;
-
-        if status.success() {
-            println!("{}", "Server stopped successfully".green());
-        } else {
-            println!("{}", "Server stopped with error".red());
+        Commands::Reload => pumpkin_runner.reload(args.skip_self_build).await,
+        Commands::Cargo { args } => pumpkin_runner.cargo(&args).await,
+        Commands::Test { args: test_args } => {
+            pumpkin_runner
+                .test(args.force, args.skip_self_build, &test_args)
+                .await
+        }
+        Commands::Check => pumpkin_runner.check().await,
+        Commands::Doc { open } => pumpkin_runner.doc(open).await,
+        Commands::Archive {
+            path,
+            artifacts_only,
+        } => pumpkin_runner.archive(&path, artifacts_only).await,
+        Commands::Restore { path } => pumpkin_runner.restore(&path).await,
+        Commands::Config {
+            format,
+            show_secrets,
+        } => pumpkin_runner.print_config(format, show_secrets),
+        Commands::Heartbeat { pid, interval } => pumpkin_runner.heartbeat(pid, interval).await,
+        Commands::Update { since, max_commits } => {
+            pumpkin_runner.update(since.as_deref(), max_commits).await
         }
-
-        Ok(())
     }
 }
@@ -0,0 +1,229 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Subset of `cargo metadata --format-version 1` output used by the runner
+#[derive(Debug, Deserialize)]
+pub struct Metadata {
+    pub packages: Vec<Package>,
+    pub workspace_members: Vec<String>,
+    pub resolve: Option<Resolve>,
+    pub target_directory: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Package {
+    pub id: String,
+    pub name: String,
+    pub targets: Vec<Target>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Target {
+    pub name: String,
+    pub crate_types: Vec<String>,
+    pub src_path: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Resolve {
+    pub nodes: Vec<Node>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Node {
+    pub id: String,
+    pub dependencies: Vec<String>,
+}
+
+impl Metadata {
+    pub fn load(dir: &Path) -> Result<Self> {
+        let output = Command::new("cargo")
+            .args(["metadata", "--format-version", "1"])
+            .current_dir(dir)
+            .output()
+            .context("Failed to execute cargo metadata")?;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "cargo metadata failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        serde_json::from_slice(&output.stdout).context("Failed to parse cargo metadata output")
+    }
+
+    /// Names of workspace member packages that build a cdylib plugin
+    pub fn workspace_plugins(&self) -> Vec<String> {
+        self.packages
+            .iter()
+            .filter(|package| self.workspace_members.contains(&package.id))
+            .filter(|package| {
+                package
+                    .targets
+                    .iter()
+                    .any(|target| target.crate_types.iter().any(|t| t == "cdylib"))
+            })
+            .map(|package| package.name.clone())
+            .collect()
+    }
+
+    /// Name of the library target of package `package`, which is what a
+    /// `[lib] name` override changes
+    pub fn lib_name(&self, package: &str) -> Option<&str> {
+        self.lib_target(package).map(|target| target.name.as_str())
+    }
+
+    /// Library target of workspace member `package`
+    pub fn lib_target(&self, package: &str) -> Option<&Target> {
+        self.packages
+            .iter()
+            .filter(|candidate| self.workspace_members.contains(&candidate.id))
+            .find(|candidate| candidate.name == package)?
+            .targets
+            .iter()
+            .find(|target| {
+                target.crate_types.iter().any(|t| {
+                    matches!(
+                        t.as_str(),
+                        "lib" | "rlib" | "dylib" | "cdylib" | "staticlib"
+                    )
+                })
+            })
+    }
+
+    /// Library targets of non-workspace dependencies that produce a cdylib
+    pub fn cdylib_dependencies(&self) -> Vec<&Target> {
+        let Some(resolve) = &self.resolve else {
+            return Vec::new();
+        };
+
+        let nodes: HashMap<&str, &Node> = resolve
+            .nodes
+            .iter()
+            .map(|node| (node.id.as_str(), node))
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut stack: Vec<&str> = self.workspace_members.iter().map(String::as_str).collect();
+        while let Some(id) = stack.pop() {
+            if let Some(node) = nodes.get(id) {
+                for dep in &node.dependencies {
+                    if seen.insert(dep.as_str()) {
+                        stack.push(dep);
+                    }
+                }
+            }
+        }
+
+        self.packages
+            .iter()
+            .filter(|package| seen.contains(package.id.as_str()))
+            .filter(|package| !self.workspace_members.contains(&package.id))
+            .flat_map(|package| &package.targets)
+            .filter(|target| target.crate_types.iter().any(|t| t == "cdylib"))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A workspace with plugin `my-plugin` (`[lib] name = "renamed"`), a
+    /// helper rlib, and a cdylib dependency reached through the helper
+    fn metadata() -> Metadata {
+        serde_json::from_str(
+            r#"{
+                "packages": [
+                    {
+                        "id": "my-plugin 0.1.0",
+                        "name": "my-plugin",
+                        "targets": [
+                            {"name": "renamed", "crate_types": ["cdylib"], "src_path": "/ws/my-plugin/src/lib.rs"},
+                            {"name": "integration", "crate_types": ["bin"], "src_path": "/ws/my-plugin/tests/integration.rs"}
+                        ]
+                    },
+                    {
+                        "id": "helper 0.1.0",
+                        "name": "helper",
+                        "targets": [
+                            {"name": "helper", "crate_types": ["lib"], "src_path": "/ws/helper/src/lib.rs"}
+                        ]
+                    },
+                    {
+                        "id": "native 1.0.0",
+                        "name": "native",
+                        "targets": [
+                            {"name": "native", "crate_types": ["cdylib", "rlib"], "src_path": "/registry/native/src/lib.rs"}
+                        ]
+                    },
+                    {
+                        "id": "unused 1.0.0",
+                        "name": "unused",
+                        "targets": [
+                            {"name": "unused", "crate_types": ["cdylib"], "src_path": "/registry/unused/src/lib.rs"}
+                        ]
+                    }
+                ],
+                "workspace_members": ["my-plugin 0.1.0", "helper 0.1.0"],
+                "resolve": {
+                    "nodes": [
+                        {"id": "my-plugin 0.1.0", "dependencies": ["helper 0.1.0"]},
+                        {"id": "helper 0.1.0", "dependencies": ["native 1.0.0"]},
+                        {"id": "native 1.0.0", "dependencies": []},
+                        {"id": "unused 1.0.0", "dependencies": []}
+                    ]
+                },
+                "target_directory": "/ws/target"
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn workspace_plugins_are_cdylib_members() {
+        assert_eq!(metadata().workspace_plugins(), ["my-plugin"]);
+    }
+
+    #[test]
+    fn lib_name_follows_the_lib_override() {
+        let metadata = metadata();
+        assert_eq!(metadata.lib_name("my-plugin"), Some("renamed"));
+        assert_eq!(metadata.lib_name("helper"), Some("helper"));
+        assert_eq!(
+            metadata
+                .lib_target("my-plugin")
+                .map(|target| &target.src_path),
+            Some(&PathBuf::from("/ws/my-plugin/src/lib.rs"))
+        );
+    }
+
+    #[test]
+    fn lib_name_ignores_non_members() {
+        let metadata = metadata();
+        assert_eq!(metadata.lib_name("native"), None);
+        assert_eq!(metadata.lib_name("missing"), None);
+    }
+
+    #[test]
+    fn cdylib_dependencies_are_transitive_and_reachable_only() {
+        let metadata = metadata();
+        let names: Vec<_> = metadata
+            .cdylib_dependencies()
+            .into_iter()
+            .map(|target| target.name.as_str())
+            .collect();
+        assert_eq!(names, ["native"]);
+    }
+
+    #[test]
+    fn cdylib_dependencies_need_a_resolve_graph() {
+        let mut metadata = metadata();
+        metadata.resolve = None;
+        assert!(metadata.cdylib_dependencies().is_empty());
+    }
+}
@@ -0,0 +1,13 @@
+use notify_rust::Notification;
+
+/// Show a desktop notification.
+///
+/// Best effort: without a notification service (CI, SSH sessions) nothing
+/// is shown and the error is ignored.
+pub fn send(summary: &str, body: &str) {
+    let _ = Notification::new()
+        .appname("cargo-pumpkin")
+        .summary(summary)
+        .body(body)
+        .show();
+}
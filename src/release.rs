@@ -0,0 +1,348 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use tokio::fs;
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+}
+
+/// `owner/repo` of a GitHub repository URL, if it is one
+pub fn github_slug(repo_url: &str) -> Option<String> {
+    let path = repo_url
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .split_once("github.com")?
+        .1
+        .trim_start_matches([':', '/']);
+
+    let mut parts = path.split('/');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(owner), Some(repo), None) if !owner.is_empty() && !repo.is_empty() => {
+            Some(format!("{}/{}", owner, repo))
+        }
+        _ => None,
+    }
+}
+
+/// HTTP client for GitHub requests, sending everything through `proxy` if given.
+///
+/// Without one, `HTTP_PROXY`/`HTTPS_PROXY` from the environment are used.
+pub fn client(proxy: Option<&str>) -> Result<reqwest::Client> {
+    let mut builder =
+        reqwest::Client::builder().user_agent(concat!("cargo-pumpkin/", env!("CARGO_PKG_VERSION")));
+    if let Some(proxy) = proxy {
+        builder = builder
+            .proxy(reqwest::Proxy::all(proxy).with_context(|| format!("Invalid proxy {}", proxy))?);
+    }
+    builder.build().context("Failed to create HTTP client")
+}
+
+/// Marker in `.run` recording the verified release binary, used to skip re-downloads
+pub const RELEASE_MARKER: &str = ".pumpkin-release";
+
+/// Outcome of [`download`]
+pub enum Download {
+    /// A new binary was downloaded; `verified` is false when no checksum was published
+    Downloaded { asset: String, verified: bool },
+    /// The binary from a previous download is still current
+    Cached { asset: String },
+}
+
+/// Download the release binary for this platform to `dest`.
+///
+/// Returns `None` when the release or a matching asset does not exist, so the
+/// caller can fall back to building from source.
+pub async fn download(
+    slug: &str,
+    tag: &str,
+    dest: &Path,
+    proxy: Option<&str>,
+) -> Result<Option<Download>> {
+    let client = client(proxy)?;
+
+    let url = if tag == "latest" {
+        format!("https://api.github.com/repos/{}/releases/latest", slug)
+    } else {
+        format!(
+            "https://api.github.com/repos/{}/releases/tags/{}",
+            slug, tag
+        )
+    };
+
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .context("Failed to query GitHub releases")?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    let release: Release = response
+        .error_for_status()
+        .context("GitHub releases request failed")?
+        .json()
+        .await
+        .context("Failed to parse GitHub release")?;
+
+    let Some(asset) = release.assets.iter().find(|asset| {
+        matches_platform(&asset.name) && !is_checksum_file(&asset.name) && !is_archive(&asset.name)
+    }) else {
+        return Ok(None);
+    };
+    let asset_label = format!("{} ({})", asset.name, release.tag_name);
+
+    let expected = expected_checksum(&client, &release, &asset.name).await?;
+
+    let marker_path = dest.with_file_name(RELEASE_MARKER);
+    let marker = format!(
+        "{} {} {}",
+        release.tag_name,
+        asset.name,
+        expected.as_deref().unwrap_or("-")
+    );
+    if let (Some(expected), true) = (&expected, dest.exists())
+        && fs::read_to_string(&marker_path).await.ok().as_deref() == Some(marker.as_str())
+        && sha256_hex(&fs::read(dest).await?) == *expected
+    {
+        return Ok(Some(Download::Cached { asset: asset_label }));
+    }
+
+    let bytes = client
+        .get(&asset.browser_download_url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to download {}", asset.name))?
+        .bytes()
+        .await
+        .with_context(|| format!("Failed to download {}", asset.name))?;
+
+    if let Some(expected) = &expected {
+        let actual = sha256_hex(&bytes);
+        if actual != *expected {
+            anyhow::bail!(
+                "Checksum mismatch for {}: expected {}, got {}",
+                asset.name,
+                expected,
+                actual
+            );
+        }
+    }
+
+    // Written next to `dest` and renamed over it, so a running server keeps its
+    // binary and an interrupted download never leaves a truncated one
+    let partial = dest.with_file_name(".pumpkin.download");
+    fs::write(&partial, &bytes)
+        .await
+        .context("Failed to write downloaded Pumpkin binary")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&partial, std::fs::Permissions::from_mode(0o755))
+            .await
+            .context("Failed to make downloaded Pumpkin binary executable")?;
+    }
+    fs::rename(&partial, dest)
+        .await
+        .context("Failed to replace Pumpkin binary")?;
+    fs::write(&marker_path, &marker)
+        .await
+        .context("Failed to write release marker")?;
+
+    Ok(Some(Download::Downloaded {
+        asset: asset_label,
+        verified: expected.is_some(),
+    }))
+}
+
+fn is_checksum_file(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.ends_with(".sha256") || name.contains("sha256sums") || name.contains("checksums")
+}
+
+/// Whether a release asset is an archive rather than a bare binary
+fn is_archive(name: &str) -> bool {
+    let name = name.to_lowercase();
+    [".zip", ".tar", ".tgz", ".gz", ".xz", ".zst", ".7z"]
+        .iter()
+        .any(|extension| name.ends_with(extension))
+}
+
+/// SHA-256 published for `asset_name`, either as `<asset>.sha256` or in a checksum list
+async fn expected_checksum(
+    client: &reqwest::Client,
+    release: &Release,
+    asset_name: &str,
+) -> Result<Option<String>> {
+    let sidecar = format!("{}.sha256", asset_name);
+    let Some(checksum_asset) = release
+        .assets
+        .iter()
+        .find(|asset| asset.name == sidecar)
+        .or_else(|| {
+            release
+                .assets
+                .iter()
+                .find(|asset| is_checksum_file(&asset.name))
+        })
+    else {
+        return Ok(None);
+    };
+
+    let content = client
+        .get(&checksum_asset.browser_download_url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to download {}", checksum_asset.name))?
+        .text()
+        .await
+        .with_context(|| format!("Failed to download {}", checksum_asset.name))?;
+
+    if let Some(hash) = parse_checksum(&content, asset_name) {
+        return Ok(Some(hash));
+    }
+
+    anyhow::bail!(
+        "{} does not contain a checksum for {}",
+        checksum_asset.name,
+        asset_name
+    )
+}
+
+/// Hash for `asset_name` in a checksum file: either a bare hash, or
+/// `<hash>  <filename>` lines as written by sha256sum (`*` marks binary mode)
+fn parse_checksum(content: &str, asset_name: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let hash = parts.next()?;
+        let file = parts.next().map(|file| file.trim_start_matches('*'));
+        let is_hex = hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit());
+        (is_hex && file.is_none_or(|file| file == asset_name)).then(|| hash.to_lowercase())
+    })
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Whether a [`download`] failure came from the network or the GitHub API,
+/// as opposed to a checksum mismatch or a local filesystem error
+pub fn is_network_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| cause.is::<reqwest::Error>())
+}
+
+/// Whether a release asset name targets the current OS and architecture
+fn matches_platform(name: &str) -> bool {
+    let name = name.to_lowercase();
+
+    let os_names: &[&str] = match std::env::consts::OS {
+        "linux" => &["linux"],
+        "macos" => &["macos", "darwin", "apple"],
+        "windows" => &["windows"],
+        _ => return false,
+    };
+    let arch_names: &[&str] = match std::env::consts::ARCH {
+        "x86_64" => &["x64", "x86_64", "amd64"],
+        "aarch64" => &["arm64", "aarch64"],
+        _ => return false,
+    };
+
+    os_names.iter().any(|os| name.contains(os)) && arch_names.iter().any(|arch| name.contains(arch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    #[test]
+    fn parse_checksum_accepts_a_bare_hash() {
+        assert_eq!(
+            parse_checksum(&format!("{}\n", HASH.to_uppercase()), "pumpkin"),
+            Some(HASH.to_string())
+        );
+    }
+
+    #[test]
+    fn parse_checksum_finds_the_asset_line() {
+        let other = "0".repeat(64);
+        let content = format!(
+            "{}  pumpkin-macos-arm64\n{} *pumpkin-linux-x64\n",
+            other, HASH
+        );
+        assert_eq!(
+            parse_checksum(&content, "pumpkin-linux-x64"),
+            Some(HASH.to_string())
+        );
+        assert_eq!(parse_checksum(&content, "pumpkin-macos-arm64"), Some(other));
+        assert_eq!(parse_checksum(&content, "pumpkin-windows-x64.exe"), None);
+    }
+
+    #[test]
+    fn parse_checksum_skips_blank_and_malformed_lines() {
+        let content = format!("\n# sha256\nnot-a-hash pumpkin\n{}  pumpkin\n", HASH);
+        assert_eq!(parse_checksum(&content, "pumpkin"), Some(HASH.to_string()));
+        assert_eq!(parse_checksum(&"z".repeat(64), "pumpkin"), None);
+    }
+
+    #[test]
+    fn sha256_hex_is_lowercase_hex() {
+        assert_eq!(sha256_hex(b"test"), HASH);
+    }
+
+    #[test]
+    fn checksum_files_are_recognized() {
+        assert!(is_checksum_file("pumpkin-linux-x64.sha256"));
+        assert!(is_checksum_file("SHA256SUMS"));
+        assert!(!is_checksum_file("pumpkin-linux-x64"));
+    }
+
+    #[test]
+    fn archives_are_not_binaries() {
+        assert!(is_archive("pumpkin-linux-x64.tar.gz"));
+        assert!(is_archive("Pumpkin-Windows-x64.ZIP"));
+        assert!(!is_archive("pumpkin-linux-x64"));
+        assert!(!is_archive("pumpkin-windows-x64.exe"));
+    }
+
+    #[test]
+    fn github_slug_reads_https_and_ssh_urls() {
+        assert_eq!(
+            github_slug("https://github.com/Pumpkin-MC/Pumpkin.git").as_deref(),
+            Some("Pumpkin-MC/Pumpkin")
+        );
+        assert_eq!(
+            github_slug("git@github.com:Pumpkin-MC/Pumpkin").as_deref(),
+            Some("Pumpkin-MC/Pumpkin")
+        );
+        assert_eq!(github_slug("https://gitlab.com/a/b"), None);
+        assert_eq!(github_slug("https://github.com/a/b/c"), None);
+    }
+
+    #[test]
+    fn is_network_error_looks_through_context() {
+        let request = reqwest::Client::new().get("not a url").build().unwrap_err();
+        let err = anyhow::Error::from(request).context("Failed to query GitHub releases");
+        assert!(is_network_error(&err));
+
+        let mismatch = anyhow::anyhow!("Checksum mismatch for pumpkin-linux-x64");
+        assert!(!is_network_error(&mismatch));
+    }
+}
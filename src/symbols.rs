@@ -0,0 +1,61 @@
+use anyhow::{Context, Result};
+use object::Object;
+use std::path::Path;
+
+/// Symbols Pumpkin looks up when loading a plugin, emitted by its plugin macro
+pub const PLUGIN_EXPORTS: [&str; 2] = ["plugin", "METADATA"];
+
+/// Names from `expected` that the dynamic library at `path` does not export
+pub fn missing_exports<'a>(path: &Path, expected: &[&'a str]) -> Result<Vec<&'a str>> {
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let file = object::File::parse(&*data)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    let exported = file
+        .exports()
+        .with_context(|| format!("Failed to read exports of {}", path.display()))?;
+
+    Ok(expected
+        .iter()
+        .copied()
+        .filter(|name| !exported.iter().any(|export| is_export(export.name(), name)))
+        .collect())
+}
+
+/// Whether the exported symbol `export` is `name`
+fn is_export(export: &[u8], name: &str) -> bool {
+    // Mach-O prefixes C symbols with an underscore
+    export == name.as_bytes() || export.strip_prefix(b"_") == Some(name.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_export_accepts_mach_o_underscores() {
+        assert!(is_export(b"plugin", "plugin"));
+        assert!(is_export(b"_METADATA", "METADATA"));
+        assert!(!is_export(b"__METADATA", "METADATA"));
+        assert!(!is_export(b"plugin_init", "plugin"));
+        assert!(!is_export(b"METADATA", "plugin"));
+    }
+
+    #[test]
+    fn missing_exports_lists_what_an_executable_lacks() {
+        let executable = std::env::current_exe().unwrap();
+        assert_eq!(
+            missing_exports(&executable, &PLUGIN_EXPORTS).unwrap(),
+            PLUGIN_EXPORTS
+        );
+    }
+
+    #[test]
+    fn missing_exports_rejects_non_objects() {
+        let path =
+            std::env::temp_dir().join(format!("cargo-pumpkin-symbols-{}.txt", std::process::id()));
+        std::fs::write(&path, "not a library").unwrap();
+        assert!(missing_exports(&path, &PLUGIN_EXPORTS).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::release;
+use colored::*;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::fs;
+
+/// How long a looked-up crates.io version is reused before asking again
+const CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Deserialize)]
+struct CrateResponse {
+    #[serde(rename = "crate")]
+    krate: CrateInfo,
+}
+
+#[derive(Deserialize)]
+struct CrateInfo {
+    max_stable_version: String,
+}
+
+/// Print an upgrade hint when crates.io has a newer cargo-pumpkin.
+///
+/// Best effort: network and cache errors are ignored so the check never
+/// gets in the way of a build.
+pub async fn check(proxy: Option<&str>) {
+    let current = env!("CARGO_PKG_VERSION");
+    let Some(latest) = latest_version(proxy).await else {
+        return;
+    };
+
+    if parse_version(&latest) > parse_version(current) {
+        eprintln!(
+            "{}",
+            format!(
+                "cargo-pumpkin {} is available (running {}), upgrade with `cargo install cargo-pumpkin`",
+                latest, current
+            )
+            .yellow()
+        );
+    }
+}
+
+fn cache_path() -> PathBuf {
+    std::env::temp_dir().join("cargo-pumpkin-latest-version")
+}
+
+/// Latest version from the cache when it is fresh, otherwise from crates.io
+async fn latest_version(proxy: Option<&str>) -> Option<String> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+
+    // The cache holds `<checked at> <version>`
+    if let Ok(cached) = fs::read_to_string(cache_path()).await
+        && let Some((checked_at, version)) = cached.trim().split_once(' ')
+        && checked_at
+            .parse::<u64>()
+            .is_ok_and(|checked_at| now.saturating_sub(checked_at) < CACHE_TTL.as_secs())
+    {
+        return Some(version.to_string());
+    }
+
+    let url = concat!("https://crates.io/api/v1/crates/", env!("CARGO_PKG_NAME"));
+    let response: CrateResponse = release::client(proxy)
+        .ok()?
+        .get(url)
+        .timeout(Duration::from_secs(3))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .ok()?
+        .json()
+        .await
+        .ok()?;
+
+    let version = response.krate.max_stable_version;
+    let _ = fs::write(cache_path(), format!("{} {}", now, version)).await;
+    Some(version)
+}
+
+/// Numeric `major.minor.patch` components, with missing ones as 0 so `1.85`
+/// equals `1.85.0`; pre-release suffixes are ignored
+pub(crate) fn parse_version(version: &str) -> Vec<u64> {
+    let mut parts: Vec<u64> = version
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect();
+    if parts.len() < 3 {
+        parts.resize(3, 0);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_version_pads_missing_components() {
+        assert_eq!(parse_version("1.85"), parse_version("1.85.0"));
+        assert_eq!(parse_version("2"), vec![2, 0, 0]);
+    }
+
+    #[test]
+    fn parse_version_drops_pre_release_and_build_metadata() {
+        assert_eq!(parse_version("1.86.0-nightly"), vec![1, 86, 0]);
+        assert_eq!(parse_version("0.3.1+build.5"), vec![0, 3, 1]);
+    }
+
+    #[test]
+    fn parse_version_orders_numerically() {
+        assert!(parse_version("0.10.0") > parse_version("0.9.9"));
+        assert!(parse_version("1.100") > parse_version("1.85.3"));
+    }
+}